@@ -0,0 +1,172 @@
+//! Basic token/slice matching parsers.
+
+use crate::{Error, ErrorMessage, Needed, Parser};
+use std::fmt::Debug;
+use std::num::NonZeroUsize;
+
+/// `Needed::Size` for exactly one more token.
+fn one_more() -> Needed {
+	Needed::Size(NonZeroUsize::new(1).unwrap())
+}
+
+/// Match a single token equal to `tag`.
+pub fn sym<'a, I>(tag: I) -> Parser<'a, I, I>
+where
+	I: Clone + PartialEq + Debug + 'a,
+{
+	Parser::new(move |input: &'a [I], start: usize| match input.get(start) {
+		Some(item) if *item == tag => Ok((item.clone(), start + 1)),
+		Some(item) => {
+			let expected = tag.clone();
+			Err(Error::mismatch(
+				start,
+				Some(ErrorMessage::new(move || format!("{:?}", expected))),
+				Some(ErrorMessage::new(move || format!("{:?}", item))),
+			))
+		}
+		None => Err(Error::Incomplete {
+			needed: one_more(),
+			position: start,
+		}),
+	})
+}
+
+/// Match a fixed sequence of tokens.
+pub fn seq<'a, I>(tags: &'static [I]) -> Parser<'a, I, &'a [I]>
+where
+	I: PartialEq + Debug + 'a,
+{
+	Parser::new(move |input: &'a [I], start: usize| {
+		let available = input.len().saturating_sub(start);
+		if available >= tags.len() {
+			let end = start + tags.len();
+			if &input[start..end] == tags {
+				Ok((&input[start..end], end))
+			} else {
+				let found = input.get(start).map(|item| ErrorMessage::new(move || format!("{:?}", item)));
+				Err(Error::mismatch(
+					start,
+					Some(ErrorMessage::new(move || format!("{:?}", tags))),
+					found,
+				))
+			}
+		} else if input[start..] == tags[..available] {
+			let needed = NonZeroUsize::new(tags.len() - available).unwrap();
+			Err(Error::Incomplete {
+				needed: Needed::Size(needed),
+				// As far as this parser legitimately got: every available
+				// token matched before input ran out.
+				position: start + available,
+			})
+		} else {
+			let found = input.get(start).map(|item| ErrorMessage::new(move || format!("{:?}", item)));
+			Err(Error::mismatch(
+				start,
+				Some(ErrorMessage::new(move || format!("{:?}", tags))),
+				found,
+			))
+		}
+	})
+}
+
+/// Consume exactly `n` tokens, regardless of their value.
+///
+/// Reports a shortfall as `Error::Incomplete` with `Needed::Size(k)`, where
+/// `k` is `n` minus the tokens still available; streaming callers can buffer
+/// exactly `k` more tokens and retry.
+pub fn take<'a, I>(n: usize) -> Parser<'a, I, &'a [I]>
+where
+	I: 'a,
+{
+	Parser::new(move |input: &'a [I], start: usize| {
+		let end = start + n;
+		if end <= input.len() {
+			Ok((&input[start..end], end))
+		} else {
+			let available = input.len().saturating_sub(start);
+			let needed = NonZeroUsize::new(n - available).unwrap();
+			Err(Error::Incomplete {
+				needed: Needed::Size(needed),
+				position: start + available,
+			})
+		}
+	})
+}
+
+/// Match a single token that appears in `set`.
+pub fn one_of<'a, I>(set: &'static [I]) -> Parser<'a, I, I>
+where
+	I: Clone + PartialEq + Debug + 'a,
+{
+	Parser::new(move |input: &'a [I], start: usize| match input.get(start) {
+		Some(item) if set.contains(item) => Ok((item.clone(), start + 1)),
+		Some(item) => Err(Error::mismatch(
+			start,
+			Some(ErrorMessage::new(move || format!("one of {:?}", set))),
+			Some(ErrorMessage::new(move || format!("{:?}", item))),
+		)),
+		None => Err(Error::Incomplete {
+			needed: one_more(),
+			position: start,
+		}),
+	})
+}
+
+/// Match a single token that does not appear in `set`.
+pub fn none_of<'a, I>(set: &'static [I]) -> Parser<'a, I, I>
+where
+	I: Clone + PartialEq + Debug + 'a,
+{
+	Parser::new(move |input: &'a [I], start: usize| match input.get(start) {
+		Some(item) if !set.contains(item) => Ok((item.clone(), start + 1)),
+		Some(item) => Err(Error::mismatch(
+			start,
+			Some(ErrorMessage::new(move || format!("none of {:?}", set))),
+			Some(ErrorMessage::new(move || format!("{:?}", item))),
+		)),
+		None => Err(Error::Incomplete {
+			needed: one_more(),
+			position: start,
+		}),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::Cell;
+
+	thread_local! {
+		static FORMAT_CALLS: Cell<usize> = const { Cell::new(0) };
+	}
+
+	#[derive(Clone, PartialEq)]
+	struct Counted(u8);
+
+	impl Debug for Counted {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			FORMAT_CALLS.with(|calls| calls.set(calls.get() + 1));
+			write!(f, "Counted({})", self.0)
+		}
+	}
+
+	#[test]
+	fn discarded_mismatch_never_formats_expected_or_found() {
+		let input = [Counted(b'x')];
+		// `sym` fails here, but the caller never inspects the error, so the
+		// lazily-wrapped expected/found Debug output must never be formatted.
+		let _ = sym(Counted(b'a')).parse(&input);
+		FORMAT_CALLS.with(|calls| assert_eq!(calls.get(), 0, "Debug formatted for a discarded error"));
+	}
+
+	#[test]
+	fn seq_reports_position_past_matched_prefix_on_shortfall() {
+		let input = [1u8, 2];
+		let parser = seq::<u8>(&[1, 2, 3]);
+		let result = parser.parse_at(&input, 0);
+		match result {
+			Err(Error::Incomplete { position, .. }) => assert_eq!(position, 2),
+			other => panic!("expected Incomplete, got {:?}", other),
+		}
+	}
+}
@@ -0,0 +1,237 @@
+//! pom is a PEG (Parsing Expression Grammar) parser combinator library for Rust.
+//!
+//! Parsers are built by combining small building blocks with operators such as
+//! `+` (sequence), `|` (alternation) and `.repeat(..)`, and are run directly
+//! against a slice of input tokens.
+
+pub mod combinator;
+mod result;
+
+pub use result::{Error, ErrorMessage, Needed, Result};
+
+use std::ops::{Add, BitOr, Shl, Shr};
+
+/// The boxed closure a `Parser` wraps: parse `input` starting at `start`,
+/// returning the parsed value and the position just past it.
+type ParseFn<'a, I, O> = Box<dyn Fn(&'a [I], usize) -> Result<'a, (O, usize)> + 'a>;
+
+/// A parser combinator.
+///
+/// `Parser<'a, I, O>` parses a slice of `I` tokens and produces an `O` on
+/// success, along with the position immediately following what it consumed.
+pub struct Parser<'a, I, O> {
+	method: ParseFn<'a, I, O>,
+}
+
+impl<'a, I, O> Parser<'a, I, O> {
+	/// Create a new parser from a closure that parses `input` starting at
+	/// `start` and returns the parsed value together with the position just
+	/// past it.
+	pub fn new<F>(parse: F) -> Parser<'a, I, O>
+	where
+		F: Fn(&'a [I], usize) -> Result<'a, (O, usize)> + 'a,
+	{
+		Parser {
+			method: Box::new(parse),
+		}
+	}
+
+	/// Apply the parser to `input`, starting at position 0.
+	pub fn parse(&self, input: &'a [I]) -> Result<'a, O> {
+		self.parse_at(input, 0).map(|(out, _)| out)
+	}
+
+	/// Apply the parser to `input` starting at `start`, returning the parsed
+	/// value and the position immediately following it.
+	pub fn parse_at(&self, input: &'a [I], start: usize) -> Result<'a, (O, usize)> {
+		(self.method)(input, start)
+	}
+}
+
+impl<'a, I: 'a, O: 'a> Parser<'a, I, O> {
+	/// Map parsed output to a new value.
+	pub fn map<U, F>(self, f: F) -> Parser<'a, I, U>
+	where
+		F: Fn(O) -> U + 'a,
+		U: 'a,
+	{
+		Parser::new(move |input, start| self.parse_at(input, start).map(|(out, pos)| (f(out), pos)))
+	}
+
+	/// Discard the parsed output, keeping only the fact that it parsed.
+	pub fn discard(self) -> Parser<'a, I, ()> {
+		self.map(|_| ())
+	}
+
+	/// Make the parser optional, succeeding with `None` if it fails without
+	/// advancing. A fatal error is still propagated.
+	pub fn opt(self) -> Parser<'a, I, Option<O>> {
+		Parser::new(move |input, start| match self.parse_at(input, start) {
+			Ok((out, pos)) => Ok((Some(out), pos)),
+			Err(err) => {
+				if err.is_fatal() {
+					Err(err)
+				} else {
+					Ok((None, start))
+				}
+			}
+		})
+	}
+
+	/// Repeat the parser `range` times, collecting the outputs into a `Vec`.
+	///
+	/// A fatal error from the inner parser is propagated immediately instead
+	/// of ending the repetition silently with whatever has been matched so
+	/// far. Falling short of `range.start` repetitions is reported as
+	/// `Error::Incomplete`, positioned at how far the repetition got. Its
+	/// `Needed` is the missing sub-parse's own, when that sub-parse failed
+	/// with `Incomplete` (i.e. input genuinely ran out) — a count of missing
+	/// *repetitions* isn't a token count, and converting one into the other
+	/// would misreport how many tokens a streaming caller actually needs to
+	/// buffer before retrying. When the sub-parse failed for some other
+	/// reason, the shortfall isn't measurable in tokens at all, so
+	/// `Needed::Unknown` is reported instead.
+	pub fn repeat(self, range: ::std::ops::Range<usize>) -> Parser<'a, I, Vec<O>> {
+		Parser::new(move |input, start| {
+			let mut items = vec![];
+			let mut pos = start;
+			let mut last_err = None;
+			while items.len() + 1 < range.end {
+				match self.parse_at(input, pos) {
+					Ok((item, next)) => {
+						items.push(item);
+						pos = next;
+					}
+					Err(err) => {
+						if err.is_fatal() {
+							return Err(err);
+						}
+						last_err = Some(err);
+						break;
+					}
+				}
+			}
+			if items.len() >= range.start {
+				Ok((items, pos))
+			} else {
+				let needed = match last_err {
+					Some(Error::Incomplete { needed, .. }) => needed,
+					_ => Needed::Unknown,
+				};
+				Err(Error::Incomplete { needed, position: pos })
+			}
+		})
+	}
+
+	/// Commit to this parser: once it fails, turn a recoverable `Mismatch` or
+	/// `Custom` error into a fatal one, so that `|` stops trying other
+	/// alternatives and `repeat` stops instead of silently returning what it
+	/// has so far.
+	pub fn cut(self) -> Parser<'a, I, O> {
+		Parser::new(move |input, start| self.parse_at(input, start).map_err(Error::cut))
+	}
+}
+
+impl<'a, I: 'a, O: 'a, U: 'a> Add<Parser<'a, I, U>> for Parser<'a, I, O> {
+	type Output = Parser<'a, I, (O, U)>;
+
+	/// Sequence: parse `self` then `other`, returning both outputs.
+	fn add(self, other: Parser<'a, I, U>) -> Self::Output {
+		Parser::new(move |input, start| {
+			let (out1, pos1) = self.parse_at(input, start)?;
+			let (out2, pos2) = other.parse_at(input, pos1)?;
+			Ok(((out1, out2), pos2))
+		})
+	}
+}
+
+impl<'a, I: 'a, O: 'a, U: 'a> Shr<Parser<'a, I, U>> for Parser<'a, I, O> {
+	type Output = Parser<'a, I, U>;
+
+	/// Sequence, keeping only the second output.
+	fn shr(self, other: Parser<'a, I, U>) -> Self::Output {
+		Parser::new(move |input, start| {
+			let (_, pos1) = self.parse_at(input, start)?;
+			other.parse_at(input, pos1)
+		})
+	}
+}
+
+impl<'a, I: 'a, O: 'a, U: 'a> Shl<Parser<'a, I, U>> for Parser<'a, I, O> {
+	type Output = Parser<'a, I, O>;
+
+	/// Sequence, keeping only the first output.
+	fn shl(self, other: Parser<'a, I, U>) -> Self::Output {
+		Parser::new(move |input, start| {
+			let (out, pos1) = self.parse_at(input, start)?;
+			let (_, pos2) = other.parse_at(input, pos1)?;
+			Ok((out, pos2))
+		})
+	}
+}
+
+impl<'a, I: 'a, O: 'a> BitOr for Parser<'a, I, O> {
+	type Output = Parser<'a, I, O>;
+
+	/// Alternation: try `self`, falling back to `other` on a recoverable
+	/// error. A fatal error from `self` is propagated without trying `other`.
+	/// A fatal error from `other` is returned as it is. When both branches
+	/// fail recoverably, the reported error is the one that advanced farthest
+	/// into the input, per `Error::merge`.
+	fn bitor(self, other: Parser<'a, I, O>) -> Self::Output {
+		Parser::new(move |input, start| match self.parse_at(input, start) {
+			Ok(out) => Ok(out),
+			Err(err1) => {
+				if err1.is_fatal() {
+					Err(err1)
+				} else {
+					other.parse_at(input, start).map_err(|err2| {
+						if err2.is_fatal() {
+							err2
+						} else {
+							Error::merge(err1, err2)
+						}
+					})
+				}
+			}
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use combinator::sym;
+
+	#[test]
+	fn cut_makes_failure_fatal_and_stops_alternation() {
+		let input = [b'a', b'x'];
+		let parser = (sym(b'a').discard() + sym(b'b').cut().discard()).discard() | sym(b'a').discard();
+		let err = parser.parse(&input).unwrap_err();
+		assert!(err.is_fatal(), "a cut failure should stay fatal through `|`: {:?}", err);
+	}
+
+	#[test]
+	fn repeat_reports_incomplete_needed_when_input_ran_out() {
+		let input = [b'a', b'a'];
+		let parser = sym(b'a').repeat(3..5);
+		let result = parser.parse(&input);
+		match result {
+			Err(Error::Incomplete { needed, .. }) => {
+				assert_eq!(needed, Needed::Size(::std::num::NonZeroUsize::new(1).unwrap()))
+			}
+			other => panic!("expected Incomplete, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn repeat_reports_needed_unknown_when_shortfall_is_not_a_token_count() {
+		let input = [b'a', b'a', b'x'];
+		let parser = sym(b'a').repeat(3..5);
+		let result = parser.parse(&input);
+		match result {
+			Err(Error::Incomplete { needed, .. }) => assert_eq!(needed, Needed::Unknown),
+			other => panic!("expected Incomplete, got {:?}", other),
+		}
+	}
+}
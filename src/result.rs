@@ -1,5 +1,26 @@
 use std::error;
 use std::fmt::{self, Display};
+use std::num::NonZeroUsize;
+
+/// How much more input a parser needs before it can make progress again.
+/// Lets streaming/partial-input callers decide whether to buffer more tokens
+/// and retry, rather than treating every shortfall identically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Needed {
+	/// At least one more token is required, but how many isn't known.
+	Unknown,
+	/// Exactly this many more tokens are required.
+	Size(NonZeroUsize),
+}
+
+impl Display for Needed {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Needed::Unknown => write!(f, "unknown amount of further input"),
+			Needed::Size(n) => write!(f, "{} more token(s)", n),
+		}
+	}
+}
 
 #[derive(Clone)]
 pub struct ErrorMessage<'a>(std::sync::Arc<dyn Fn() -> String + 'a>);
@@ -30,10 +51,23 @@ impl<'a> Display for ErrorMessage<'a> {
 /// Parser error.
 #[derive(Debug, Clone)]
 pub enum Error<'a> {
-	Incomplete,
+	/// Input ran out before the parser could finish; `Needed` says how much
+	/// more is required, so streaming callers know whether to buffer more
+	/// tokens and retry rather than treat this as a hard failure. `position`
+	/// is how far the parser had genuinely gotten before running out, so
+	/// alternation can still rank it against other branches' failures
+	/// instead of treating every `Incomplete` as having failed at byte 0.
+	Incomplete { needed: Needed, position: usize },
 	Mismatch {
 		message: ErrorMessage<'a>,
 		position: usize,
+		/// The token/slice a parser expected to find, e.g. `` `)` ``. Lazy:
+		/// the `Debug` formatting that produces this text only runs if the
+		/// error is actually reported.
+		expected: Option<ErrorMessage<'a>>,
+		/// The token actually found at `position`, e.g. `` `;` ``. Lazy for
+		/// the same reason as `expected`.
+		found: Option<ErrorMessage<'a>>,
 	},
 	Conversion {
 		message: ErrorMessage<'a>,
@@ -49,15 +83,31 @@ pub enum Error<'a> {
 		position: usize,
 		inner: Option<Box<Error<'a>>>,
 	},
+	/// A recoverable error that has been committed to by `.cut()`. Alternation
+	/// and repetition propagate this immediately instead of backtracking.
+	Fatal(Box<Error<'a>>),
+	/// Several alternatives failed at the same, farthest-reached position.
+	/// Produced by merging branch failures in `|`; see `Error::merge`.
+	Alternatives {
+		position: usize,
+		expected: Vec<ErrorMessage<'a>>,
+	},
 }
 
 impl<'a> Error<'a> {
 	pub fn evaluate(self) -> Error<'static> {
 		match self {
-			Error::Incomplete => Error::Incomplete,
-			Error::Mismatch { message, position } => Error::Mismatch {
+			Error::Incomplete { needed, position } => Error::Incomplete { needed, position },
+			Error::Mismatch {
+				message,
+				position,
+				expected,
+				found,
+			} => Error::Mismatch {
 				message: message.evaluate(),
 				position,
+				expected: expected.map(|message| message.evaluate()),
+				found: found.map(|message| message.evaluate()),
 			},
 			Error::Conversion { message, position } => Error::Conversion {
 				message: message.evaluate(),
@@ -81,18 +131,211 @@ impl<'a> Error<'a> {
 				position,
 				inner: inner.map(|inner| Box::new(inner.evaluate())),
 			},
+			Error::Fatal(inner) => Error::Fatal(Box::new(inner.evaluate())),
+			Error::Alternatives { position, expected } => Error::Alternatives {
+				position,
+				expected: expected.into_iter().map(|message| message.evaluate()).collect(),
+			},
+		}
+	}
+
+	/// Whether this error has been committed to by `.cut()`. A fatal error must
+	/// not be discarded by alternation or repetition; it has to propagate to
+	/// the caller instead.
+	pub fn is_fatal(&self) -> bool {
+		matches!(self, Error::Fatal(_))
+	}
+
+	/// Commit to this error: a recoverable `Mismatch` or `Custom` becomes
+	/// fatal, so `|` stops trying other alternatives and `repeat` stops
+	/// instead of quietly returning what it has so far. Other variants already
+	/// carry enough context on their own and are returned unchanged.
+	pub fn cut(self) -> Error<'a> {
+		match self {
+			Error::Mismatch { .. } | Error::Custom { .. } => Error::Fatal(Box::new(self)),
+			err => err,
+		}
+	}
+
+	/// Build a `Mismatch` whose message is rendered lazily from `expected` and
+	/// `found`, e.g. "expected `)`, found `;`". Token/slice matching parsers
+	/// (`sym`, `seq`, `one_of`, ...) use this instead of hand-writing a
+	/// message closure; parsers that want custom wording can still construct
+	/// `Error::Mismatch` directly.
+	///
+	/// `expected`/`found` are themselves `ErrorMessage`s rather than `String`s
+	/// so that callers can defer the (often `Debug`-formatting) work of
+	/// producing them until the message is actually displayed, instead of
+	/// paying for it on every failed match attempt.
+	pub fn mismatch(position: usize, expected: Option<ErrorMessage<'a>>, found: Option<ErrorMessage<'a>>) -> Error<'a> {
+		let message = {
+			let expected = expected.clone();
+			let found = found.clone();
+			ErrorMessage::new(move || match (&expected, &found) {
+				(Some(expected), Some(found)) => format!("expected {}, found {}", expected, found),
+				(Some(expected), None) => format!("expected {}", expected),
+				(None, Some(found)) => format!("unexpected {}", found),
+				(None, None) => "mismatch".to_string(),
+			})
+		};
+		Error::Mismatch {
+			message,
+			position,
+			expected,
+			found,
+		}
+	}
+
+	/// Byte offset this error was reported at. Used to rank alternation
+	/// branches by how far each one advanced into the input before failing.
+	pub fn position(&self) -> usize {
+		match self {
+			Error::Incomplete { position, .. } => *position,
+			Error::Mismatch { position, .. }
+			| Error::Conversion { position, .. }
+			| Error::Expect { position, .. }
+			| Error::Custom { position, .. }
+			| Error::Alternatives { position, .. } => *position,
+			Error::Fatal(inner) => inner.position(),
+		}
+	}
+
+	/// The set of expected-token messages this error stands for, flattening
+	/// an existing `Alternatives` instead of nesting it inside another one.
+	fn into_expected(self) -> Vec<ErrorMessage<'a>> {
+		match self {
+			Error::Alternatives { expected, .. } => expected,
+			err => vec![err.expected_message()],
+		}
+	}
+
+	/// This branch's own "what was expected" text, e.g. `` `)` `` or
+	/// "identifier", without the position/variant-name scaffolding that
+	/// `Display` wraps it in. Used to build `Alternatives`'s expected-set so
+	/// `expected one of: a, b, c at N` reads as a list of short phrases
+	/// instead of a list of full, position-qualified error messages.
+	fn expected_message(&self) -> ErrorMessage<'a> {
+		match self {
+			Error::Mismatch { expected: Some(expected), .. } => expected.clone(),
+			Error::Mismatch { message, .. }
+			| Error::Conversion { message, .. }
+			| Error::Expect { message, .. }
+			| Error::Custom { message, .. } => message.clone(),
+			Error::Incomplete { needed, .. } => {
+				let needed = *needed;
+				ErrorMessage::new(move || needed.to_string())
+			}
+			Error::Fatal(inner) => inner.expected_message(),
+			Error::Alternatives { .. } => {
+				let text = self.to_string();
+				ErrorMessage::new(move || text.clone())
+			}
+		}
+	}
+
+	/// Merge two alternation branch failures using the PEG longest-match
+	/// heuristic: the branch that advanced farthest into the input before
+	/// failing wins outright. On a tie, their expected messages are combined
+	/// (deduplicated) into a single `Alternatives` error, so a chain of
+	/// `p1 | p2 | p3` collapses all tied candidates into one deduplicated
+	/// expected-set instead of reporting just the last branch tried.
+	pub fn merge(a: Error<'a>, b: Error<'a>) -> Error<'a> {
+		let (position_a, position_b) = (a.position(), b.position());
+		if position_a > position_b {
+			a
+		} else if position_b > position_a {
+			b
+		} else {
+			let position = position_a;
+			let mut expected = a.into_expected();
+			for message in b.into_expected() {
+				let message_text = message.to_string();
+				if !expected.iter().any(|existing| existing.to_string() == message_text) {
+					expected.push(message);
+				}
+			}
+			Error::Alternatives { position, expected }
+		}
+	}
+
+	/// Render a human-readable diagnostic: the error message followed by the
+	/// offending source line with a caret under the column it was reported
+	/// at, recursing into `Expect`/`Custom` to show a short error chain.
+	/// `Fatal` is unwrapped transparently since it isn't a cause of its own.
+	/// Accepts either `&[u8]` or `&str` input.
+	pub fn render<S: AsRef<[u8]> + ?Sized>(&self, input: &S) -> String {
+		let mut out = String::new();
+		self.render_into(input.as_ref(), &mut out);
+		out
+	}
+
+	fn render_into(&self, input: &[u8], out: &mut String) {
+		// `Fatal` is a transparent marker, not a layer of its own: its
+		// `Display` already just forwards to `inner`, so rendering it
+		// directly avoids printing the same message/line/caret twice.
+		if let Error::Fatal(inner) = self {
+			return inner.render_into(input, out);
+		}
+		let (line, column, source_line) = locate(input, self.position());
+		out.push_str(&format!("{} at line {}, column {}\n", self.own_message(), line, column));
+		out.push_str(&source_line);
+		out.push('\n');
+		for _ in 1..column {
+			out.push(' ');
+		}
+		out.push_str("^\n");
+		match self {
+			Error::Expect { inner, .. } | Error::Custom { inner: Some(inner), .. } => {
+				out.push_str("caused by: ");
+				inner.render_into(input, out);
+			}
+			_ => {}
+		}
+	}
+
+	/// This error's own message, without the inner cause that `Expect`'s and
+	/// `Custom`'s `Display` embed inline — `render_into` displays that cause
+	/// itself on the next line, so including it here would show it twice.
+	fn own_message(&self) -> String {
+		match self {
+			Error::Expect { message, position, .. } | Error::Custom { message, position, .. } => {
+				format!("{} at {}", message, position)
+			}
+			other => other.to_string(),
 		}
 	}
 }
 
+/// Convert a byte offset into a 1-based `(line, column)` and the text of the
+/// source line it falls on.
+fn locate(input: &[u8], position: usize) -> (usize, usize, String) {
+	let position = position.min(input.len());
+	let mut line = 1;
+	let mut line_start = 0;
+	for (i, &byte) in input[..position].iter().enumerate() {
+		if byte == b'\n' {
+			line += 1;
+			line_start = i + 1;
+		}
+	}
+	let line_end = input[line_start..]
+		.iter()
+		.position(|&byte| byte == b'\n')
+		.map_or(input.len(), |i| line_start + i);
+	let column = position - line_start + 1;
+	(line, column, String::from_utf8_lossy(&input[line_start..line_end]).into_owned())
+}
+
 impl<'a, 'b> PartialEq<Error<'b>> for Error<'a> {
 	fn eq(&self, other: &Error<'b>) -> bool {
 		match (self, other) {
-			(Error::Incomplete, Error::Incomplete) => true,
+			(Error::Incomplete { needed: a, .. }, Error::Incomplete { needed: b, .. }) => a == b,
 			(Error::Mismatch { .. }, Error::Mismatch { .. }) => true,
 			(Error::Conversion { .. }, Error::Conversion { .. }) => true,
 			(Error::Expect { .. }, Error::Expect { .. }) => true,
 			(Error::Custom { .. }, Error::Custom { .. }) => true,
+			(Error::Fatal(a), Error::Fatal(b)) => a == b,
+			(Error::Alternatives { .. }, Error::Alternatives { .. }) => true,
 			_ => false,
 		}
 	}
@@ -107,10 +350,11 @@ impl<'a> error::Error for Error<'a> {
 impl<'a> Display for Error<'a> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
-			Error::Incomplete => write!(f, "Incomplete"),
+			Error::Incomplete { needed, position } => write!(f, "Incomplete at {}: need {}", position, needed),
 			Error::Mismatch {
 				ref message,
 				ref position,
+				..
 			} => write!(f, "Mismatch at {}: {}", position, message),
 			Error::Conversion {
 				ref message,
@@ -131,9 +375,119 @@ impl<'a> Display for Error<'a> {
 				ref position,
 				inner: None,
 			} => write!(f, "{} at {}", message, position),
+			Error::Fatal(ref inner) => write!(f, "{}", inner),
+			Error::Alternatives {
+				ref position,
+				ref expected,
+			} => {
+				write!(f, "expected one of: ")?;
+				for (i, message) in expected.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{}", message)?;
+				}
+				write!(f, " at {}", position)
+			}
 		}
 	}
 }
 
 /// Parser result, `Result<O>` ia alias of `Result<O, pom::Error>`.
 pub type Result<'a, O> = ::std::result::Result<O, Error<'a>>;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn msg(text: &'static str) -> ErrorMessage<'static> {
+		ErrorMessage::new(move || text.to_string())
+	}
+
+	#[test]
+	fn merge_prefers_farthest_position() {
+		let near = Error::Mismatch {
+			message: msg("a"),
+			position: 1,
+			expected: None,
+			found: None,
+		};
+		let far = Error::Incomplete {
+			needed: Needed::Unknown,
+			position: 5,
+		};
+		assert_eq!(Error::merge(near, far).position(), 5);
+	}
+
+	#[test]
+	fn merge_dedupes_identical_tied_branches() {
+		let a = Error::Mismatch {
+			message: msg("identifier"),
+			position: 3,
+			expected: None,
+			found: None,
+		};
+		let b = Error::Mismatch {
+			message: msg("identifier"),
+			position: 3,
+			expected: None,
+			found: None,
+		};
+		match Error::merge(a, b) {
+			Error::Alternatives { position, expected } => {
+				assert_eq!(position, 3);
+				assert_eq!(expected.len(), 1, "identical branch failures should be deduplicated");
+			}
+			other => panic!("expected Alternatives, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn merge_accumulates_distinct_tied_branches() {
+		let a = Error::Mismatch {
+			message: msg("number"),
+			position: 3,
+			expected: None,
+			found: None,
+		};
+		let b = Error::Mismatch {
+			message: msg("identifier"),
+			position: 3,
+			expected: None,
+			found: None,
+		};
+		match Error::merge(a, b) {
+			Error::Alternatives { expected, .. } => assert_eq!(expected.len(), 2),
+			other => panic!("expected Alternatives, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn merge_renders_bare_expected_text_not_full_branch_display() {
+		let a = Error::mismatch(3, Some(msg("number")), None);
+		let b = Error::mismatch(3, Some(msg("identifier")), None);
+		assert_eq!(Error::merge(a, b).to_string(), "expected one of: number, identifier at 3");
+	}
+
+	#[test]
+	fn incomplete_position_is_not_always_zero() {
+		let incomplete = Error::Incomplete {
+			needed: Needed::Unknown,
+			position: 7,
+		};
+		assert_eq!(incomplete.position(), 7);
+	}
+
+	#[test]
+	fn render_does_not_duplicate_fatal_inner() {
+		let inner = Error::mismatch(4, Some(msg("`)`")), Some(msg("`;`")));
+		let fatal = Error::Fatal(Box::new(inner));
+		let rendered = fatal.render("abcd;");
+		assert_eq!(
+			rendered.matches("expected").count(),
+			1,
+			"Fatal should render its inner error once, not duplicate it: {}",
+			rendered
+		);
+	}
+}